@@ -0,0 +1,121 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Configuration for autorebase.
+///
+/// Loaded from `autorebase.toml` in the repo root, or failing that
+/// `.git/autorebase/autorebase.toml`. Any field left out of the file falls back to its
+/// built-in default, so an empty or partial file is fine.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// The branch other branches are diffed/rebased against.
+    #[serde(default = "default_target_branch")]
+    pub target_branch: String,
+
+    /// Which `RevisionProvider` to use: `"arc"` or `"github"`. Overridden
+    /// by `--provider`.
+    #[serde(default = "default_provider")]
+    pub provider: String,
+
+    /// Status -> color name, merged over the built-in map below (see
+    /// `Config::load`, which does the merging - `serde(default)` only
+    /// covers the key being absent entirely, not overriding one entry).
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+
+    /// Glob patterns of branches to include. Empty means "everything",
+    /// unless narrowed by `exclude`.
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Glob patterns of branches to exclude, applied after `include`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            target_branch: default_target_branch(),
+            provider: default_provider(),
+            colors: default_colors(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+fn default_target_branch() -> String {
+    "master".to_owned()
+}
+
+fn default_provider() -> String {
+    "arc".to_owned()
+}
+
+fn default_colors() -> HashMap<String, String> {
+    [
+        // Arcanist differential statuses.
+        ("Closed", "cyan"),
+        ("Needs Review", "magenta"),
+        ("Needs Revision", "red"),
+        ("Changes Planned", "red"),
+        ("Accepted", "green"),
+        ("No Revision", "blue"),
+        ("Abandoned", "dimmed"),
+        // GitHub pull request statuses ("Closed" is shared with Arcanist above).
+        ("Merged", "cyan"),
+        ("Draft", "blue"),
+        ("Approved", "green"),
+        ("Open", "magenta"),
+    ]
+    .iter()
+    .map(|(status, color)| (status.to_string(), color.to_string()))
+    .collect()
+}
+
+/// Merge a user-supplied `colors` map over the built-in defaults so that
+/// overriding one status doesn't drop the colors for the rest.
+fn merge_colors(overrides: HashMap<String, String>) -> HashMap<String, String> {
+    let mut colors = default_colors();
+    colors.extend(overrides);
+    colors
+}
+
+impl Config {
+    /// Search `autorebase.toml` in `working_dir`, then
+    /// `.git/autorebase/autorebase.toml`, falling back to built-in
+    /// defaults if neither is present.
+    pub fn load(working_dir: &Path) -> Result<Config> {
+        for path in [
+            working_dir.join("autorebase.toml"),
+            working_dir.join(".git/autorebase/autorebase.toml"),
+        ] {
+            if path.exists() {
+                let contents = std::fs::read_to_string(&path)?;
+                let mut config: Config = toml::from_str(&contents)?;
+                config.colors = merge_colors(config.colors);
+                return Ok(config);
+            }
+        }
+
+        Ok(Config::default())
+    }
+
+    /// Whether `branch` passes the `include`/`exclude` glob filters.
+    pub fn branch_included(&self, branch: &str) -> bool {
+        if self.exclude.iter().any(|pattern| glob_match(pattern, branch)) {
+            return false;
+        }
+
+        self.include.is_empty() || self.include.iter().any(|pattern| glob_match(pattern, branch))
+    }
+}
+
+fn glob_match(pattern: &str, branch: &str) -> bool {
+    glob::Pattern::new(pattern)
+        .map(|pattern| pattern.matches(branch))
+        .unwrap_or(false)
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use crate::trim::TrimAsciiWhitespace;
+use git_commands::git;
+use serde::Serialize;
+use std::path::Path;
+
+/// How far a branch has diverged from another ref: commits only on the
+/// branch (`ahead`) and commits only on the other ref (`behind`).
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct AheadBehind {
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Parse the `%(upstream:track)` value from `for-each-ref`, e.g.
+/// `"[ahead 2, behind 1]"`, `"[ahead 2]"`, `"[behind 1]"`, `"[gone]"`, or
+/// `""` (no upstream, or up to date with it).
+pub fn parse_upstream_track(track: &str) -> AheadBehind {
+    AheadBehind {
+        ahead: extract_count(track, "ahead "),
+        behind: extract_count(track, "behind "),
+    }
+}
+
+fn extract_count(track: &str, prefix: &str) -> usize {
+    track
+        .split_once(prefix)
+        .and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|digits| digits.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Commits `branch` is ahead/behind `target`, via a single `git rev-list
+/// --left-right --count` call.
+pub fn target_divergence(working_dir: &Path, target: &str, branch: &str) -> Result<AheadBehind> {
+    let output = git(
+        &[
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("{}...{}", target, branch),
+        ],
+        working_dir,
+    )?
+    .stdout;
+    let output = std::str::from_utf8(output.trim_ascii_whitespace())?;
+
+    let mut counts = output.split_whitespace();
+    // Left side is commits unique to `target` i.e. how far behind it `branch` is;
+    // right side is commits unique to `branch` i.e. how far ahead it is.
+    let behind = counts.next().unwrap_or("0").parse().unwrap_or(0);
+    let ahead = counts.next().unwrap_or("0").parse().unwrap_or(0);
+
+    Ok(AheadBehind { ahead, behind })
+}
@@ -1,152 +1,158 @@
-use anyhow::{anyhow, bail, Result};
-use colored::{ColoredString, Colorize};
-use std::path::Path;
-use url::Url;
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use git_commands::git;
-use std::process::Command;
-use std::collections::HashMap;
 
+mod config;
+mod divergence;
+mod providers;
+mod render;
+mod scan;
 mod trim;
+use config::Config;
+use divergence::AheadBehind;
+use providers::RevisionProvider;
+use render::BranchReport;
 use trim::TrimAsciiWhitespace;
 
-macro_rules! regex {
-    ($re:literal $(,)?) => {{
-        static RE: once_cell::sync::OnceCell<regex::Regex> = once_cell::sync::OnceCell::new();
-        RE.get_or_init(|| regex::Regex::new($re).unwrap())
-    }};
+/// List branches and their in-flight differential revisions.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Emit a JSON array instead of the colored table, for editor
+    /// plugins, launchers, or scripts.
+    #[arg(long)]
+    json: bool,
+
+    /// Recursively scan this directory for repositories (any directory
+    /// containing a `.git` entry) instead of only inspecting the current
+    /// directory. May be given multiple times.
+    #[arg(long = "scan")]
+    scan_paths: Vec<PathBuf>,
+
+    /// Skip repositories under a directory with this name while
+    /// scanning. May be given multiple times.
+    #[arg(long = "ignore")]
+    ignored_names: Vec<String>,
+
+    /// Which `RevisionProvider` to use: `"arc"` or `"github"`. Overrides
+    /// `Config::provider`.
+    #[arg(long)]
+    provider: Option<String>,
 }
 
 fn main() -> Result<()> {
-    // Get the list of git branches and the diff numbers for them.
-    let working_dir = std::env::current_dir()?;
+    let cli = Cli::parse();
 
-    let arc_info = get_arc_info(&working_dir)?;
-
-    let branches = get_branches(&working_dir)?;
-
-    let max_branch_len = branches.iter().map(|branch| branch.branch.len()).max().unwrap_or_default();
-
-    for branch in branches {
-        let diffs = get_branch_diffs(&working_dir, &branch.branch, "master")?;
-
-        print!(
-            "{:width$}",
-            if diffs.is_empty() { branch.branch.normal() } else { branch.branch.bold() },
-            width = max_branch_len + 2,
-        );
+    if cli.json || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
 
-        for diff in diffs {
-            print!(" {}", diff.bold());
-            if let Some(info) = arc_info.get(&diff) {
-                print!(" ({})", coloured_status(&info.status));
+    let scanning = !cli.scan_paths.is_empty();
+
+    let repos: Vec<PathBuf> = if scanning {
+        cli.scan_paths
+            .iter()
+            .flat_map(|path| scan::find_repos(path, &cli.ignored_names))
+            .collect()
+    } else {
+        vec![std::env::current_dir()?]
+    };
+
+    // One bad repo (missing `arc`, a target branch that doesn't exist, a
+    // non-GitHub remote with `provider = "github"`, ...) shouldn't stop
+    // `--scan` from reporting on the rest, so errors are logged to
+    // stderr and skipped rather than propagated.
+    let mut had_error = false;
+
+    if cli.json {
+        let mut all_reports = Vec::new();
+        for repo in &repos {
+            match reports_for_repo(repo, &cli) {
+                Ok((reports, _config)) => all_reports.extend(reports),
+                Err(error) => {
+                    eprintln!("{}: {:#}", repo.display(), error);
+                    had_error = true;
+                }
             }
         }
+        render::print_json(&all_reports)?;
+    } else {
+        for repo in &repos {
+            if scanning {
+                println!("{}", repo.display().to_string().bold());
+            }
 
-        println!();
+            match reports_for_repo(repo, &cli) {
+                Ok((reports, config)) => render::print_human(&reports, &config.colors),
+                Err(error) => {
+                    eprintln!("{}: {:#}", repo.display(), error);
+                    had_error = true;
+                }
+            }
+        }
     }
-    Ok(())
-}
-
-// Get the output of `arc list`.
-fn arc_list(working_dir: &Path) -> Result<Vec<String>> {
-    let output = Command::new("arc")
-        .args(&["list"])
-        .current_dir(working_dir)
-        .output()?;
 
-    if !output.status.success() {
-        bail!("arc list command failed: {:?}", output);
+    if had_error {
+        bail!("one or more repositories failed, see above");
     }
 
-    let output = std::str::from_utf8(output.stdout.trim_ascii_whitespace())?;
-
-    Ok(output.lines().map(|line| line.to_owned()).collect())
-}
-
-#[derive(Debug)]
-struct ArcInfo {
-    // Not sure what this means.
-    exists: bool,
-    //   'Closed'          => 'cyan',
-    //   'Needs Review'    => 'magenta',
-    //   'Needs Revision'  => 'red',
-    //   'Changes Planned' => 'red',
-    //   'Accepted'        => 'green',
-    //   'No Revision'     => 'blue',
-    //   'Abandoned'       => 'default',
-    status: String,
-    summary: String,
+    Ok(())
 }
 
-fn coloured_status(status: &str) -> ColoredString {
-    match status {
-        "Closed"          => status.cyan(),
-        "Needs Review"    => status.magenta(),
-        "Needs Revision"  => status.red(),
-        "Changes Planned" => status.red(),
-        "Accepted"        => status.green(),
-        "No Revision"     => status.blue(),
-        "Abandoned"       => status.dimmed(),
-        _                 => status.normal(),
-    }
+/// Load a repo's config, pick its provider, and build its branch
+/// reports - the work `main` does per repo, pulled out so errors for one
+/// repo can be caught without aborting the rest of a `--scan`.
+fn reports_for_repo(working_dir: &Path, cli: &Cli) -> Result<(Vec<BranchReport>, Config)> {
+    let config = Config::load(working_dir)?;
+    let provider_name = cli.provider.as_deref().unwrap_or(&config.provider);
+    let provider = providers::make_provider(provider_name, working_dir)?;
+    let reports = build_reports(working_dir, &config, provider.as_ref())?;
+    Ok((reports, config))
 }
 
-fn get_arc_info(working_dir: &Path) -> Result<HashMap<String, ArcInfo>> {
-    // Output of `arc list` is:
-
-    // 1. "You have no open Differential revisions." if you have no open diffs.
-    // 2. A table with the columns:
-    //     * Exists (an asterisk or blank)
-    //     * Status ("Needs Review" etc)
-    //     * Title ("D1234: Foo bar")
-    //
-    // Unfortunately the table is not fixed width - it depends on the content.
-    // Easy solution is a regex.
-
-    let lines = arc_list(working_dir)?;
-
-    if lines == &["You have no open Differential revisions."] {
-        return Ok(HashMap::new());
-    }
-
-    lines.iter().map(|line| {
-        let re = regex!(r#"^(?P<exists>\* )?(?P<status>[\w ]+) (?P<diff>D\d+): (?P<summary>.*)$"#);
-        let caps = re.captures(line).ok_or_else(|| anyhow!("Couldn't parse line: {:?}", line))?;
-
-        let exists = caps.name("exists").is_some();
-        let status = caps["status"].trim().to_owned();
-        let diff = caps["diff"].to_owned();
-        let summary = caps["summary"].trim().to_owned();
-
-        Ok((
-            diff,
-            ArcInfo{
-                exists,
-                status,
-                summary,
-            },
-        ))
-    }).collect::<Result<_, _>>()
+/// Build one `BranchReport` per branch, combining `get_branches` with the
+/// revisions `provider` finds for each branch.
+fn build_reports(working_dir: &Path, config: &Config, provider: &dyn RevisionProvider) -> Result<Vec<BranchReport>> {
+    let branches = get_branches(working_dir)?
+        .into_iter()
+        .filter(|branch| config.branch_included(&branch.branch));
+
+    branches
+        .map(|branch| {
+            let merge_base = get_merge_base(working_dir, &branch.branch, &config.target_branch)?;
+            let commit_bodies = get_commit_bodies(working_dir, &merge_base, &branch.branch)?;
+            let diffs = provider.revisions_for_branch(&branch.branch, &commit_bodies)?;
+            let target_divergence =
+                divergence::target_divergence(working_dir, &config.target_branch, &branch.branch)?;
+
+            Ok(BranchReport {
+                repo: working_dir.display().to_string(),
+                branch: branch.branch,
+                upstream: branch.upstream,
+                upstream_divergence: branch.upstream_divergence,
+                target_divergence,
+                diffs,
+            })
+        })
+        .collect()
 }
 
-
-
 #[derive(Debug)]
 struct BranchInfo {
     branch: String,
     upstream: Option<String>,
+    upstream_divergence: AheadBehind,
 }
 
 fn get_branches(working_dir: &Path) -> Result<Vec<BranchInfo>> {
     use std::str;
 
-    // TODO: Config system to allow specifying the branches? Maybe allow adding/removing them?
-    // Store config in `.git/autorebase/autorebase.toml` or `autorebase.toml`?
-
     let output = git(
         &[
             "for-each-ref",
-            "--format=%(refname:short)%00%(upstream:short)",
+            "--format=%(refname:short)%00%(upstream:short)%00%(upstream:track)",
             "refs/heads",
         ],
         working_dir,
@@ -158,7 +164,7 @@ fn get_branches(working_dir: &Path) -> Result<Vec<BranchInfo>> {
         .filter(|line| !line.is_empty())
         .map(|line| {
             let parts: Vec<&[u8]> = line.split(|c| *c == 0).collect();
-            if parts.len() != 2 {
+            if parts.len() != 3 {
                 bail!(
                     "for-each-ref parse error, got {} parts, expected 3",
                     parts.len()
@@ -173,9 +179,12 @@ fn get_branches(working_dir: &Path) -> Result<Vec<BranchInfo>> {
                 Some(str::from_utf8(parts[1])?.to_owned())
             };
 
+            let upstream_divergence = divergence::parse_upstream_track(str::from_utf8(parts[2])?);
+
             Ok(BranchInfo {
                 branch,
                 upstream,
+                upstream_divergence,
             })
         })
         .collect::<Result<_, _>>()?;
@@ -183,14 +192,6 @@ fn get_branches(working_dir: &Path) -> Result<Vec<BranchInfo>> {
     Ok(branches)
 }
 
-fn get_branch_diffs(working_dir: &Path, branch: &str, target_branch: &str) -> Result<Vec<String>> {
-    let merge_base = get_merge_base(working_dir, branch, target_branch)?;
-
-    let bodies = get_commit_bodies(working_dir, &merge_base, branch)?;
-
-    Ok(bodies.iter().rev().filter_map(|s| get_differential_revision(s)).collect())
-}
-
 fn get_merge_base(working_dir: &Path, a: &str, b: &str) -> Result<String> {
     let output = git(&["merge-base", a, b], working_dir)?.stdout;
     let output = std::str::from_utf8(output.trim_ascii_whitespace())?;
@@ -214,19 +215,3 @@ fn get_commit_bodies(working_dir: &Path, from: &str, to: &str) -> Result<Vec<Str
     let output = String::from_utf8(output)?;
     Ok(output.lines().map(ToOwned::to_owned).collect())
 }
-
-/// If the line is of the form "Differential revision: http(s)://.../D1234" then
-/// return Some("D1234").
-fn get_differential_revision(line: &str) -> Option<String> {
-    if let Some(url_str) = line.strip_prefix("Differential Revision:") {
-        if let Ok(url) = Url::parse(url_str.trim()) {
-            let path = url.path();
-            if let Some(diff_number) = path.strip_prefix("/D") {
-                if diff_number.chars().all(|c| c.is_ascii_digit()) {
-                    return Some(path[1..].to_owned());
-                }
-            }
-        }
-    }
-    None
-}
@@ -0,0 +1,123 @@
+use super::{RevisionInfo, RevisionProvider};
+use crate::trim::TrimAsciiWhitespace;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use url::Url;
+
+macro_rules! regex {
+    ($re:literal $(,)?) => {{
+        static RE: once_cell::sync::OnceCell<regex::Regex> = once_cell::sync::OnceCell::new();
+        RE.get_or_init(|| regex::Regex::new($re).unwrap())
+    }};
+}
+
+/// The Arcanist backend: reads `Differential Revision:` trailers out of
+/// commit bodies and looks their status up in `arc list`.
+pub struct ArcProvider {
+    arc_info: HashMap<String, ArcInfo>,
+}
+
+impl ArcProvider {
+    pub fn new(working_dir: &Path) -> Result<Self> {
+        Ok(ArcProvider { arc_info: get_arc_info(working_dir)? })
+    }
+}
+
+impl RevisionProvider for ArcProvider {
+    fn revisions_for_branch(&self, _branch: &str, commit_bodies: &[String]) -> Result<Vec<RevisionInfo>> {
+        Ok(commit_bodies
+            .iter()
+            .rev()
+            .filter_map(|line| get_differential_revision(line))
+            .map(|id| {
+                let info = self.arc_info.get(&id);
+                RevisionInfo {
+                    id,
+                    status: info.map(|info| info.status.clone()),
+                    summary: info.map(|info| info.summary.clone()),
+                }
+            })
+            .collect())
+    }
+}
+
+// Get the output of `arc list`.
+fn arc_list(working_dir: &Path) -> Result<Vec<String>> {
+    let output = Command::new("arc")
+        .args(&["list"])
+        .current_dir(working_dir)
+        .output()?;
+
+    if !output.status.success() {
+        bail!("arc list command failed: {:?}", output);
+    }
+
+    let output = std::str::from_utf8(output.stdout.trim_ascii_whitespace())?;
+
+    Ok(output.lines().map(|line| line.to_owned()).collect())
+}
+
+#[derive(Debug)]
+struct ArcInfo {
+    // Not sure what this means.
+    exists: bool,
+    // See `config::default_colors` for the status -> color mapping.
+    status: String,
+    summary: String,
+}
+
+fn get_arc_info(working_dir: &Path) -> Result<HashMap<String, ArcInfo>> {
+    // Output of `arc list` is:
+
+    // 1. "You have no open Differential revisions." if you have no open diffs.
+    // 2. A table with the columns:
+    //     * Exists (an asterisk or blank)
+    //     * Status ("Needs Review" etc)
+    //     * Title ("D1234: Foo bar")
+    //
+    // Unfortunately the table is not fixed width - it depends on the content.
+    // Easy solution is a regex.
+
+    let lines = arc_list(working_dir)?;
+
+    if lines == &["You have no open Differential revisions."] {
+        return Ok(HashMap::new());
+    }
+
+    lines.iter().map(|line| {
+        let re = regex!(r#"^(?P<exists>\* )?(?P<status>[\w ]+) (?P<diff>D\d+): (?P<summary>.*)$"#);
+        let caps = re.captures(line).ok_or_else(|| anyhow!("Couldn't parse line: {:?}", line))?;
+
+        let exists = caps.name("exists").is_some();
+        let status = caps["status"].trim().to_owned();
+        let diff = caps["diff"].to_owned();
+        let summary = caps["summary"].trim().to_owned();
+
+        Ok((
+            diff,
+            ArcInfo{
+                exists,
+                status,
+                summary,
+            },
+        ))
+    }).collect::<Result<_, _>>()
+}
+
+/// If the line is of the form "Differential Revision: http(s)://.../D1234" then
+/// return Some("D1234").
+fn get_differential_revision(line: &str) -> Option<String> {
+    if let Some(url_str) = line.strip_prefix("Differential Revision:") {
+        if let Ok(url) = Url::parse(url_str.trim()) {
+            let path = url.path();
+            if let Some(diff_number) = path.strip_prefix("/D") {
+                if diff_number.chars().all(|c| c.is_ascii_digit()) {
+                    return Some(path[1..].to_owned());
+                }
+            }
+        }
+    }
+    None
+}
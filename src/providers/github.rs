@@ -0,0 +1,155 @@
+use super::{RevisionInfo, RevisionProvider};
+use crate::trim::TrimAsciiWhitespace;
+use anyhow::{anyhow, Result};
+use git_commands::git;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The GitHub backend: looks up pull requests (open, closed or merged)
+/// whose head branch matches the branch being reported on.
+pub struct GithubProvider {
+    owner: String,
+    repo: String,
+    token: Option<String>,
+}
+
+impl GithubProvider {
+    /// Build a provider for the repo at `working_dir`, reading
+    /// `owner/repo` from its `origin` remote and an optional API token
+    /// from `GITHUB_TOKEN`.
+    pub fn new(working_dir: &Path) -> Result<Self> {
+        let output = git(&["remote", "get-url", "origin"], working_dir)?.stdout;
+        let url = std::str::from_utf8(output.trim_ascii_whitespace())?;
+
+        let (owner, repo) = parse_github_remote(url)
+            .ok_or_else(|| anyhow!("'{}' doesn't look like a GitHub remote", url))?;
+
+        Ok(GithubProvider {
+            owner,
+            repo,
+            token: std::env::var("GITHUB_TOKEN").ok(),
+        })
+    }
+
+    fn get(&self, url: &str) -> Result<ureq::Response> {
+        let mut request = ureq::get(url).set("User-Agent", "autorebase");
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        request.call().map_err(|error| match error {
+            ureq::Error::Status(403, response) | ureq::Error::Status(429, response) => anyhow!(
+                "GitHub API rate limit hit (HTTP {}) calling {}; set GITHUB_TOKEN to raise the \
+                 limit, or wait and try again",
+                response.status(),
+                url,
+            ),
+            error => anyhow!("GitHub API request to {} failed: {}", url, error),
+        })
+    }
+
+    fn pull_request_status(&self, pull: &PullRequest) -> Result<String> {
+        if pull.merged_at.is_some() {
+            return Ok("Merged".to_owned());
+        }
+        if pull.state == "closed" {
+            return Ok("Closed".to_owned());
+        }
+        if pull.draft {
+            return Ok("Draft".to_owned());
+        }
+        if self.has_approval(pull.number)? {
+            return Ok("Approved".to_owned());
+        }
+        Ok("Open".to_owned())
+    }
+
+    /// Whether `number` is currently approved: at least one reviewer's
+    /// latest verdict is `APPROVED` and none is `CHANGES_REQUESTED`.
+    /// Reviews are considered in submission order, and `COMMENTED`/
+    /// `DISMISSED` reviews are ignored so they can't hide a reviewer's
+    /// actual latest verdict.
+    fn has_approval(&self, number: u64) -> Result<bool> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/reviews",
+            encode(&self.owner),
+            encode(&self.repo),
+            number
+        );
+        let reviews: Vec<Review> = self.get(&url)?.into_json()?;
+
+        let mut latest_verdict_by_reviewer: HashMap<String, String> = HashMap::new();
+        for review in &reviews {
+            if review.state == "APPROVED" || review.state == "CHANGES_REQUESTED" {
+                latest_verdict_by_reviewer.insert(review.user.login.clone(), review.state.clone());
+            }
+        }
+
+        let verdicts: Vec<&str> = latest_verdict_by_reviewer.values().map(String::as_str).collect();
+        Ok(verdicts.contains(&"APPROVED") && !verdicts.contains(&"CHANGES_REQUESTED"))
+    }
+}
+
+fn parse_github_remote(url: &str) -> Option<(String, String)> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("https://github.com/"))
+        .or_else(|| url.strip_prefix("http://github.com/"))?;
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.split_once('/')?;
+    Some((owner.to_owned(), repo.to_owned()))
+}
+
+/// Percent-encode a single URL path/query segment (branch name, owner,
+/// or repo - any of which may contain non-ASCII or reserved characters
+/// like `#`).
+fn encode(segment: &str) -> String {
+    utf8_percent_encode(segment, NON_ALPHANUMERIC).to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    state: String,
+    draft: bool,
+    merged_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Review {
+    state: String,
+    user: ReviewUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewUser {
+    login: String,
+}
+
+impl RevisionProvider for GithubProvider {
+    fn revisions_for_branch(&self, branch: &str, _commit_bodies: &[String]) -> Result<Vec<RevisionInfo>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls?head={}:{}&state=all",
+            encode(&self.owner),
+            encode(&self.repo),
+            encode(&self.owner),
+            encode(branch),
+        );
+
+        let pulls: Vec<PullRequest> = self.get(&url)?.into_json()?;
+
+        pulls
+            .iter()
+            .map(|pull| {
+                Ok(RevisionInfo {
+                    id: format!("#{}", pull.number),
+                    status: Some(self.pull_request_status(pull)?),
+                    summary: Some(pull.title.clone()),
+                })
+            })
+            .collect()
+    }
+}
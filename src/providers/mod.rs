@@ -0,0 +1,43 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::path::Path;
+
+mod arc;
+mod github;
+
+pub use arc::ArcProvider;
+pub use github::GithubProvider;
+
+/// A revision (code review ticket, pull request, ...) referenced by a
+/// branch, with whatever status info the backend has for it. `status`
+/// and `summary` are `None` when the branch references a revision the
+/// backend doesn't know about.
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionInfo {
+    pub id: String,
+    pub status: Option<String>,
+    pub summary: Option<String>,
+}
+
+/// Looks up the revisions associated with a branch, abstracting over the
+/// backend that actually tracks them (Arcanist differentials, GitHub
+/// pull requests, ...).
+pub trait RevisionProvider {
+    /// `commit_bodies` are the lines of the branch's commit message
+    /// bodies, newest commit first as `git log` prints them, from the
+    /// branch tip back to (not including) the merge-base - what the Arcanist
+    /// backend greps for `Differential Revision:` trailers. The GitHub
+    /// backend instead matches pull requests by `branch` and ignores
+    /// `commit_bodies`.
+    fn revisions_for_branch(&self, branch: &str, commit_bodies: &[String]) -> Result<Vec<RevisionInfo>>;
+}
+
+/// Construct the provider named by `name` (from `--provider` or
+/// `Config::provider`).
+pub fn make_provider(name: &str, working_dir: &Path) -> Result<Box<dyn RevisionProvider>> {
+    match name {
+        "arc" => Ok(Box::new(ArcProvider::new(working_dir)?)),
+        "github" => Ok(Box::new(GithubProvider::new(working_dir)?)),
+        other => bail!("Unknown provider '{}', expected 'arc' or 'github'", other),
+    }
+}
@@ -0,0 +1,75 @@
+use crate::divergence::AheadBehind;
+use crate::providers::RevisionInfo;
+use anyhow::Result;
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// One branch's row in the report: which repo it's in (so `--scan` +
+/// `--json` output can distinguish same-named branches across repos),
+/// its name, upstream (if any), how far it's diverged from that upstream
+/// and from the target branch, and the revisions its provider found for
+/// it.
+#[derive(Debug, Serialize)]
+pub struct BranchReport {
+    pub repo: String,
+    pub branch: String,
+    pub upstream: Option<String>,
+    pub upstream_divergence: AheadBehind,
+    pub target_divergence: AheadBehind,
+    pub diffs: Vec<RevisionInfo>,
+}
+
+/// Print `reports` as the original colored human-readable table, with
+/// upstream/target ahead-behind columns after the branch name.
+pub fn print_human(reports: &[BranchReport], colors: &HashMap<String, String>) {
+    let max_branch_len = reports.iter().map(|report| report.branch.len()).max().unwrap_or_default();
+
+    for report in reports {
+        print!(
+            "{:width$}",
+            if report.diffs.is_empty() { report.branch.normal() } else { report.branch.bold() },
+            width = max_branch_len + 2,
+        );
+
+        print!(" {}", render_divergence(&report.upstream_divergence));
+        print!(" {}", render_divergence(&report.target_divergence));
+
+        for diff in &report.diffs {
+            print!(" {}", diff.id.bold());
+            if let Some(status) = &diff.status {
+                print!(" ({})", coloured_status(status, colors));
+            }
+        }
+
+        println!();
+    }
+}
+
+/// Render e.g. `↑2 ↓1`, dimming either arrow whose count is zero.
+fn render_divergence(divergence: &AheadBehind) -> String {
+    format!(
+        "{} {}",
+        render_count('↑', divergence.ahead),
+        render_count('↓', divergence.behind),
+    )
+}
+
+fn render_count(arrow: char, count: usize) -> ColoredString {
+    let text = format!("{}{}", arrow, count);
+    if count == 0 { text.dimmed() } else { text.normal() }
+}
+
+/// Print `reports` as a JSON array, one object per branch.
+pub fn print_json(reports: &[BranchReport]) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(reports)?);
+    Ok(())
+}
+
+fn coloured_status(status: &str, colors: &HashMap<String, String>) -> ColoredString {
+    match colors.get(status).map(String::as_str) {
+        Some("dimmed") => status.dimmed(),
+        Some(color) => status.color(color),
+        None => status.normal(),
+    }
+}
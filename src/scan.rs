@@ -0,0 +1,24 @@
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Find repositories under `root`: any directory containing a `.git`
+/// entry. Directories named `.git` itself, or matching a name in
+/// `ignore`, are not recursed into - except `root` itself, so an
+/// `--ignore` name that happens to match a `--scan` root's own directory
+/// name doesn't prune the whole scan.
+pub fn find_repos(root: &Path, ignore: &[String]) -> Vec<PathBuf> {
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
+        if entry.depth() == 0 {
+            return true;
+        }
+
+        let name = entry.file_name().to_str().unwrap_or("");
+        name != ".git" && !ignore.iter().any(|ignored| ignored == name)
+    });
+
+    walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir() && entry.path().join(".git").exists())
+        .map(|entry| entry.path().to_path_buf())
+        .collect()
+}